@@ -1,5 +1 @@
-mod sqlite;
-
-fn main() {
-    println!("Hello, world!");
-}
+pub mod sqlite;
@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use rusqlite::{ffi, Connection, Error, Result};
+
+/// Attaches the database file at `path` to `conn` under the schema name
+/// `alias`.
+///
+/// Tables in the attached database can then be referenced as `alias.table`,
+/// including in joins with tables of the main database.
+///
+/// `alias` must be a plain identifier (ASCII letters, digits and `_`, not
+/// starting with a digit) so that it can be used unquoted in qualified names.
+pub fn attach<P: AsRef<Path>>(conn: &Connection, path: P, alias: &str) -> Result<()> {
+    validate_alias(alias)?;
+
+    let path = path.as_ref();
+    let path = path
+        .to_str()
+        .ok_or_else(|| Error::InvalidPath(path.to_owned()))?;
+
+    conn.execute("ATTACH DATABASE ?1 AS ?2", [path, alias])?;
+
+    Ok(())
+}
+
+/// Detaches the database previously attached as `alias`.
+pub fn detach(conn: &Connection, alias: &str) -> Result<()> {
+    validate_alias(alias)?;
+
+    conn.execute("DETACH DATABASE ?1", [alias])?;
+
+    Ok(())
+}
+
+fn validate_alias(alias: &str) -> Result<()> {
+    let mut chars = alias.chars();
+    let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !valid {
+        return Err(Error::SqliteFailure(
+            ffi::Error::new(ffi::SQLITE_MISUSE),
+            Some(format!("invalid database alias: {:?}", alias)),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("njord-{}-{}.db", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn attached_tables_can_be_joined_and_detached() {
+        let other = temp_path("attach-join");
+        Connection::open(&other)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE prices (item_id INTEGER, price INTEGER);
+                 INSERT INTO prices VALUES (1, 10), (2, 20);",
+            )
+            .unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE items (id INTEGER, name TEXT);
+             INSERT INTO items VALUES (1, 'oar'), (2, 'sail');",
+        )
+        .unwrap();
+
+        attach(&conn, &other, "other").unwrap();
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM other.prices", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let price: i64 = conn
+            .query_row(
+                "SELECT p.price FROM items i JOIN other.prices p ON p.item_id = i.id \
+                 WHERE i.name = 'sail'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(price, 20);
+
+        detach(&conn, "other").unwrap();
+
+        assert!(conn.prepare("SELECT * FROM other.prices").is_err());
+        drop(conn);
+        std::fs::remove_file(&other).unwrap();
+    }
+
+    #[test]
+    fn rejects_aliases_that_are_not_identifiers() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        for alias in ["", "a b; DROP", "1st", "x-y", "\"quoted\""] {
+            assert!(attach(&conn, ":memory:", alias).is_err(), "{:?}", alias);
+        }
+        assert!(attach(&conn, ":memory:", "_scratch2").is_ok());
+    }
+}
@@ -0,0 +1,114 @@
+use std::os::raw::c_int;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+
+use rusqlite::functions::{Aggregate, Context, FunctionFlags};
+use rusqlite::{Connection, Result, ToSql};
+
+/// Registers a scalar SQL function named `name` taking `n_arg` arguments
+/// (`-1` for any number) on `conn`.
+///
+/// The function is available to every query executed on `conn`.
+pub fn register_function<F, T>(
+    conn: &Connection,
+    name: &str,
+    n_arg: c_int,
+    flags: FunctionFlags,
+    f: F,
+) -> Result<()>
+where
+    F: FnMut(&Context<'_>) -> Result<T> + Send + UnwindSafe + 'static,
+    T: ToSql,
+{
+    conn.create_scalar_function(name, n_arg, flags, f)
+}
+
+/// Registers an aggregate SQL function named `name` taking `n_arg` arguments
+/// (`-1` for any number) on `conn`.
+pub fn register_aggregate<A, D, T>(
+    conn: &Connection,
+    name: &str,
+    n_arg: c_int,
+    flags: FunctionFlags,
+    aggregate: D,
+) -> Result<()>
+where
+    A: RefUnwindSafe + UnwindSafe,
+    D: Aggregate<A, T> + 'static,
+    T: ToSql,
+{
+    conn.create_aggregate_function(name, n_arg, flags, aggregate)
+}
+
+/// Removes a function previously registered with the same `name` and `n_arg`.
+pub fn unregister_function(conn: &Connection, name: &str, n_arg: c_int) -> Result<()> {
+    conn.remove_function(name, n_arg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Product;
+
+    impl Aggregate<i64, i64> for Product {
+        fn init(&self, _: &mut Context<'_>) -> Result<i64> {
+            Ok(1)
+        }
+
+        fn step(&self, ctx: &mut Context<'_>, acc: &mut i64) -> Result<()> {
+            *acc *= ctx.get::<i64>(0)?;
+            Ok(())
+        }
+
+        fn finalize(&self, _: &mut Context<'_>, acc: Option<i64>) -> Result<i64> {
+            Ok(acc.unwrap_or(1))
+        }
+    }
+
+    fn deterministic() -> FunctionFlags {
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC
+    }
+
+    #[test]
+    fn scalar_function_is_callable_from_sql() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_function(&conn, "shout", 1, deterministic(), |ctx| {
+            Ok(ctx.get::<String>(0)?.to_uppercase())
+        })
+        .unwrap();
+
+        let value: String = conn
+            .query_row("SELECT shout('njord')", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(value, "NJORD");
+    }
+
+    #[test]
+    fn aggregate_runs_over_rows() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE numbers (n INTEGER);
+             INSERT INTO numbers VALUES (2), (3), (7);",
+        )
+        .unwrap();
+        register_aggregate(&conn, "product", 1, deterministic(), Product).unwrap();
+
+        let value: i64 = conn
+            .query_row("SELECT product(n) FROM numbers", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(value, 42);
+    }
+
+    #[test]
+    fn unregistered_function_no_longer_resolves() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_function(&conn, "one", 0, deterministic(), |_| Ok(1)).unwrap();
+        unregister_function(&conn, "one", 0).unwrap();
+
+        let result = conn.query_row("SELECT one()", [], |row| row.get::<_, i64>(0));
+
+        assert!(result.is_err());
+    }
+}
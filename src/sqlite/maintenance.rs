@@ -0,0 +1,114 @@
+use std::path::Path;
+
+use rusqlite::{Connection, DatabaseName, Result};
+
+/// Copies the main database of `src` to the file at `dest` using SQLite's
+/// online backup API, so `src` stays usable while the copy is made.
+///
+/// Any existing database at `dest` is overwritten by the copy.
+pub fn backup<P: AsRef<Path>>(src: &Connection, dest: P) -> Result<()> {
+    src.backup(DatabaseName::Main, dest, None)
+}
+
+/// Rebuilds the database file, reclaiming unused pages.
+///
+/// SQLite refuses to vacuum inside an open transaction, so this returns an
+/// error when called from within [`with_transaction`](super::with_transaction)
+/// or [`with_savepoint`](super::with_savepoint).
+pub fn vacuum(conn: &Connection) -> Result<()> {
+    conn.execute_batch("VACUUM")
+}
+
+/// Runs `PRAGMA integrity_check` and returns the problems it reports.
+///
+/// An empty vector means the database passed the check.
+pub fn integrity_check(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+
+    if messages.len() == 1 && messages[0] == "ok" {
+        return Ok(Vec::new());
+    }
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("njord-{}-{}.db", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn values(conn: &Connection) -> Vec<i64> {
+        let mut stmt = conn
+            .prepare("SELECT value FROM items ORDER BY value")
+            .unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.collect::<Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn backup_copies_rows() {
+        let src = Connection::open_in_memory().unwrap();
+        src.execute_batch(
+            "CREATE TABLE items (value INTEGER);
+             INSERT INTO items VALUES (1), (2), (3);",
+        )
+        .unwrap();
+        let dest = temp_path("backup-copies-rows");
+
+        backup(&src, &dest).unwrap();
+
+        let copy = Connection::open(&dest).unwrap();
+        assert_eq!(values(&copy), vec![1, 2, 3]);
+        drop(copy);
+        std::fs::remove_file(&dest).unwrap();
+    }
+
+    #[test]
+    fn backup_overwrites_existing_file() {
+        let dest = temp_path("backup-overwrites");
+        Connection::open(&dest)
+            .unwrap()
+            .execute_batch("CREATE TABLE stale (value INTEGER)")
+            .unwrap();
+        let src = Connection::open_in_memory().unwrap();
+        src.execute_batch("CREATE TABLE items (value INTEGER); INSERT INTO items VALUES (7);")
+            .unwrap();
+
+        backup(&src, &dest).unwrap();
+
+        let copy = Connection::open(&dest).unwrap();
+        assert_eq!(values(&copy), vec![7]);
+        assert!(copy.prepare("SELECT * FROM stale").is_err());
+        drop(copy);
+        std::fs::remove_file(&dest).unwrap();
+    }
+
+    #[test]
+    fn integrity_check_passes_on_healthy_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE items (value INTEGER); INSERT INTO items VALUES (1);")
+            .unwrap();
+
+        assert_eq!(integrity_check(&conn).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn vacuum_fails_inside_transaction() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        vacuum(&conn).unwrap();
+
+        let tx = conn.transaction().unwrap();
+
+        assert!(vacuum(&tx).is_err());
+    }
+}
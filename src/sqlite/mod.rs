@@ -1,2 +1,12 @@
 /*pub use connection::SqliteConnection;
 pub use statement::SqliteStatement;*/
+mod attach;
+mod functions;
+mod maintenance;
+mod transaction;
+
+pub use attach::{attach, detach};
+pub use functions::{register_aggregate, register_function, unregister_function};
+pub use maintenance::{backup, integrity_check, vacuum};
+pub use rusqlite::functions::{Aggregate, Context, FunctionFlags};
+pub use transaction::{with_savepoint, with_transaction, SavepointSource};
@@ -0,0 +1,178 @@
+use rusqlite::{Connection, Result as SqliteResult, Savepoint, Transaction};
+
+/// Anything a savepoint can be opened on: a connection, a transaction or
+/// another savepoint.
+pub trait SavepointSource {
+    fn open_savepoint(&mut self) -> SqliteResult<Savepoint<'_>>;
+}
+
+impl SavepointSource for Connection {
+    fn open_savepoint(&mut self) -> SqliteResult<Savepoint<'_>> {
+        self.savepoint()
+    }
+}
+
+impl SavepointSource for Transaction<'_> {
+    fn open_savepoint(&mut self) -> SqliteResult<Savepoint<'_>> {
+        self.savepoint()
+    }
+}
+
+impl SavepointSource for Savepoint<'_> {
+    fn open_savepoint(&mut self) -> SqliteResult<Savepoint<'_>> {
+        self.savepoint()
+    }
+}
+
+/// Runs `f` inside a transaction on `conn`.
+///
+/// The transaction is committed if `f` returns `Ok` and rolled back if it
+/// returns `Err` or panics. Nested units of work can be run on the
+/// transaction with [`with_savepoint`].
+///
+/// This helper is synchronous and only covers the sqlite backend.
+pub fn with_transaction<T, E, F>(conn: &mut Connection, f: F) -> Result<T, E>
+where
+    F: FnOnce(&mut Transaction) -> Result<T, E>,
+    E: From<rusqlite::Error>,
+{
+    let mut tx = conn.transaction()?;
+    // dropping `tx` without committing rolls it back, which also covers unwinding
+    let value = f(&mut tx)?;
+    tx.commit()?;
+
+    Ok(value)
+}
+
+/// Runs `f` inside a savepoint opened on `conn`, which may be a connection,
+/// a transaction or another savepoint.
+///
+/// The savepoint is released if `f` returns `Ok`. If `f` returns `Err` or
+/// panics, only the work done since the savepoint was opened is rolled back,
+/// and the enclosing transaction stays usable.
+///
+/// Like [`with_transaction`], this helper is synchronous.
+pub fn with_savepoint<C, T, E, F>(conn: &mut C, f: F) -> Result<T, E>
+where
+    C: SavepointSource + ?Sized,
+    F: FnOnce(&mut Savepoint) -> Result<T, E>,
+    E: From<rusqlite::Error>,
+{
+    let mut sp = conn.open_savepoint()?;
+    // dropping `sp` without committing rolls back to it, which also covers unwinding
+    let value = f(&mut sp)?;
+    sp.commit()?;
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use super::*;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE items (value INTEGER)")
+            .unwrap();
+        conn
+    }
+
+    fn values(conn: &Connection) -> Vec<i64> {
+        let mut stmt = conn
+            .prepare("SELECT value FROM items ORDER BY value")
+            .unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.collect::<SqliteResult<_>>().unwrap()
+    }
+
+    #[test]
+    fn commits_on_ok() {
+        let mut conn = setup();
+
+        let result: SqliteResult<()> = with_transaction(&mut conn, |tx| {
+            tx.execute("INSERT INTO items VALUES (1)", [])?;
+            Ok(())
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(values(&conn), vec![1]);
+    }
+
+    #[test]
+    fn rolls_back_on_err() {
+        let mut conn = setup();
+
+        let result: SqliteResult<()> = with_transaction(&mut conn, |tx| {
+            tx.execute("INSERT INTO items VALUES (1)", [])?;
+            Err(rusqlite::Error::InvalidQuery)
+        });
+
+        assert!(result.is_err());
+        assert!(values(&conn).is_empty());
+    }
+
+    #[test]
+    fn rolls_back_on_panic() {
+        let mut conn = setup();
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _: SqliteResult<()> = with_transaction(&mut conn, |tx| {
+                tx.execute("INSERT INTO items VALUES (1)", [])?;
+                panic!("unit of work failed");
+            });
+        }));
+
+        assert!(result.is_err());
+        assert!(values(&conn).is_empty());
+    }
+
+    #[test]
+    fn nested_savepoint_rolls_back_only_its_own_work() {
+        let mut conn = setup();
+
+        let result: SqliteResult<()> = with_transaction(&mut conn, |tx| {
+            tx.execute("INSERT INTO items VALUES (1)", [])?;
+
+            let inner: SqliteResult<()> = with_savepoint(tx, |sp| {
+                sp.execute("INSERT INTO items VALUES (2)", [])?;
+                Err(rusqlite::Error::InvalidQuery)
+            });
+            assert!(inner.is_err());
+
+            with_savepoint(tx, |sp| {
+                sp.execute("INSERT INTO items VALUES (3)", [])?;
+                with_savepoint(sp, |inner| {
+                    inner.execute("INSERT INTO items VALUES (4)", [])?;
+                    Ok(())
+                })
+            })
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(values(&conn), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn nested_savepoint_rolls_back_on_panic() {
+        let mut conn = setup();
+
+        let result: SqliteResult<()> = with_transaction(&mut conn, |tx| {
+            tx.execute("INSERT INTO items VALUES (1)", [])?;
+
+            let panicked = catch_unwind(AssertUnwindSafe(|| {
+                let _: SqliteResult<()> = with_savepoint(&mut *tx, |sp| {
+                    sp.execute("INSERT INTO items VALUES (2)", [])?;
+                    panic!("nested unit of work failed");
+                });
+            }));
+            assert!(panicked.is_err());
+
+            Ok(())
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(values(&conn), vec![1]);
+    }
+}